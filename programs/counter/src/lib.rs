@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 use ephemeral_rollups_sdk::anchor::{commit, delegate, ephemeral};
 use ephemeral_rollups_sdk::cpi::DelegateConfig;
 use ephemeral_rollups_sdk::ephem::{commit_accounts, commit_and_undelegate_accounts};
@@ -26,6 +28,12 @@ pub mod typing_speed_game {
         session.ended_at = None;
         session.wpm = 0;
         session.accuracy = 0;
+        session.recent_word_ts = [0; WORD_WINDOW_SIZE];
+        session.cost_units = 0;
+        session.words_at_last_commit = 0;
+        session.last_commit_ts = clock.unix_timestamp;
+        session.active_race = None;
+        session.consumed = false;
 
         msg!(
             "PDA {} initialized - Typing session started for player: {}",
@@ -71,6 +79,27 @@ pub mod typing_speed_game {
         let session = &mut ctx.accounts.session;
         require!(session.is_active, TypingError::SessionNotActive);
 
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+
+        // `Clock::unix_timestamp` only has whole-second resolution, so a single
+        // consecutive-pair gap can't express sub-second cadence. Instead, average
+        // over a sliding window of the last WORD_WINDOW_SIZE words: once the window
+        // is full, check the WPM implied by typing WORD_WINDOW_SIZE words since the
+        // timestamp about to be evicted.
+        if session.words_typed >= WORD_WINDOW_SIZE as u32 {
+            let slot = (session.words_typed as usize) % WORD_WINDOW_SIZE;
+            let window_start = session.recent_word_ts[slot];
+            let elapsed = (now - window_start).max(1) as u64;
+            let implied_wpm = (WORD_WINDOW_SIZE as u64 * 60) / elapsed;
+            require!(
+                implied_wpm <= MAX_PLAUSIBLE_WPM as u64,
+                TypingError::ImplausibleSpeed
+            );
+        }
+        session.recent_word_ts[(session.words_typed as usize) % WORD_WINDOW_SIZE] = now;
+        session.cost_units = session.cost_units.checked_add(COST_UNITS_PER_WORD).unwrap();
+
         session.words_typed = session.words_typed.checked_add(1).unwrap();
 
         if is_correct {
@@ -204,6 +233,12 @@ pub mod typing_speed_game {
         Ok(())
     }
 
+    /// Read-only view of a session's accumulated cost units
+    /// Lets a client or ER validator budget how many `type_word` calls fit before a `commit`
+    pub fn session_cost(ctx: Context<SessionView>) -> Result<u32> {
+        Ok(ctx.accounts.session.cost_units)
+    }
+
     // ========================================
     // MagicBlock Ephemeral Rollups Functions
     // ========================================
@@ -234,17 +269,22 @@ pub mod typing_speed_game {
             &ctx.accounts.magic_program,
         )?;
 
+        let clock = Clock::get()?;
+        let session = &mut ctx.accounts.session;
+        session.words_at_last_commit = session.words_typed;
+        session.last_commit_ts = clock.unix_timestamp;
+
         msg!(
             "Checkpoint: Player {} | Words: {} | Accuracy: {}%",
-            ctx.accounts.session.player,
-            ctx.accounts.session.words_typed,
-            ctx.accounts.session.accuracy
+            session.player,
+            session.words_typed,
+            session.accuracy
         );
 
         emit!(TypingCheckpoint {
-            player: ctx.accounts.session.player,
-            words_typed: ctx.accounts.session.words_typed,
-            accuracy: ctx.accounts.session.accuracy,
+            player: session.player,
+            words_typed: session.words_typed,
+            accuracy: session.accuracy,
         });
 
         Ok(())
@@ -259,16 +299,662 @@ pub mod typing_speed_game {
             &ctx.accounts.magic_context,
             &ctx.accounts.magic_program,
         )?;
+
+        let clock = Clock::get()?;
+        let session = &mut ctx.accounts.session;
+        session.words_at_last_commit = session.words_typed;
+        session.last_commit_ts = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Permissionless checkpoint: any signer can pay to commit a session, but only
+    /// once it has actually drifted enough to be worth it (word count or time threshold)
+    pub fn crank_commit(ctx: Context<CrankCommit>) -> Result<()> {
+        let clock = Clock::get()?;
+        let words_since_commit = ctx
+            .accounts
+            .session
+            .words_typed
+            .saturating_sub(ctx.accounts.session.words_at_last_commit);
+        let time_since_commit = clock.unix_timestamp - ctx.accounts.session.last_commit_ts;
+
+        require!(
+            words_since_commit >= COMMIT_WORD_THRESHOLD
+                || time_since_commit >= COMMIT_INTERVAL_SECONDS,
+            TypingError::CommitNotWarranted
+        );
+
+        commit_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.session.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+
+        let session = &mut ctx.accounts.session;
+        session.words_at_last_commit = session.words_typed;
+        session.last_commit_ts = clock.unix_timestamp;
+
+        msg!(
+            "PDA {} - Cranked checkpoint by {} | Words: {}",
+            session.key(),
+            ctx.accounts.payer.key(),
+            session.words_typed
+        );
+
+        emit!(TypingCheckpoint {
+            player: session.player,
+            words_typed: session.words_typed,
+            accuracy: session.accuracy,
+        });
+
+        Ok(())
+    }
+
+    // ========================================
+    // Wagering: token-staked typing duels
+    // ========================================
+
+    /// Stake tokens into a head-to-head match escrow
+    /// First call for a (player_a, player_b, nonce) triple creates the escrow and vault;
+    /// the second player's call just tops up their side of the bonded ledger
+    pub fn stake(
+        ctx: Context<Stake>,
+        player_a: Pubkey,
+        player_b: Pubkey,
+        nonce: u64,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, TypingError::InsufficientStake);
+
+        let signer_key = ctx.accounts.player.key();
+        require!(
+            signer_key == player_a || signer_key == player_b,
+            TypingError::InvalidPlayer
+        );
+
+        let escrow = &mut ctx.accounts.match_escrow;
+        if !escrow.initialized {
+            escrow.initialized = true;
+            escrow.player_a = player_a;
+            escrow.player_b = player_b;
+            escrow.nonce = nonce;
+            escrow.mint = ctx.accounts.mint.key();
+            escrow.bump = ctx.bumps.match_escrow;
+            escrow.vault_bump = ctx.bumps.vault;
+            escrow.bonded_a = 0;
+            escrow.bonded_b = 0;
+            escrow.settled = false;
+            escrow.winner = None;
+            escrow.active = 0;
+            escrow.unlocking = Vec::new();
+        } else {
+            // `vault` is derived from the caller-supplied `mint`, so a later stake call
+            // with a different mint would resolve to a different token account than the
+            // one escrow.mint/settle_match/withdraw assume
+            require!(
+                ctx.accounts.mint.key() == escrow.mint,
+                TypingError::MintMismatch
+            );
+        }
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.player_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.player.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        if signer_key == escrow.player_a {
+            escrow.bonded_a = escrow.bonded_a.checked_add(amount).unwrap();
+        } else {
+            escrow.bonded_b = escrow.bonded_b.checked_add(amount).unwrap();
+        }
+
+        msg!(
+            "PDA {} - Staked {} by {} (bonded_a: {}, bonded_b: {})",
+            escrow.key(),
+            amount,
+            signer_key,
+            escrow.bonded_a,
+            escrow.bonded_b
+        );
+
+        emit!(MatchStaked {
+            match_escrow: escrow.key(),
+            player: signer_key,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Settle a finished match by comparing both players' typing sessions
+    /// Winner is decided by higher wpm, tie-broken by accuracy; credits the full pot
+    /// to the winner's `active` balance, to be released later via `unbond`/`withdraw`
+    pub fn settle_match(ctx: Context<SettleMatch>) -> Result<()> {
+        let escrow = &mut ctx.accounts.match_escrow;
+        require!(!escrow.settled, TypingError::MatchAlreadySettled);
+        require!(
+            escrow.bonded_a > 0 && escrow.bonded_b > 0,
+            TypingError::InsufficientStake
+        );
+
+        let session_a = &ctx.accounts.session_a;
+        let session_b = &ctx.accounts.session_b;
+
+        require!(
+            session_a.player == escrow.player_a && session_b.player == escrow.player_b,
+            TypingError::MismatchedSessionOwner
+        );
+        require!(
+            !session_a.is_active && !session_b.is_active,
+            TypingError::SessionStillActive
+        );
+        require!(
+            !session_a.consumed && !session_b.consumed,
+            TypingError::SessionAlreadyConsumed
+        );
+
+        let winner = if session_a.wpm != session_b.wpm {
+            if session_a.wpm > session_b.wpm {
+                escrow.player_a
+            } else {
+                escrow.player_b
+            }
+        } else if session_a.accuracy != session_b.accuracy {
+            if session_a.accuracy > session_b.accuracy {
+                escrow.player_a
+            } else {
+                escrow.player_b
+            }
+        } else {
+            // Fully tied: fall back to a deterministic, stake-independent tiebreak
+            if escrow.player_a.to_bytes() < escrow.player_b.to_bytes() {
+                escrow.player_a
+            } else {
+                escrow.player_b
+            }
+        };
+
+        let pot = escrow.bonded_a.checked_add(escrow.bonded_b).unwrap();
+        escrow.winner = Some(winner);
+        escrow.active = pot;
+        escrow.settled = true;
+        ctx.accounts.session_a.consumed = true;
+        ctx.accounts.session_b.consumed = true;
+
+        msg!(
+            "PDA {} - Match settled | Winner: {} | Pot: {}",
+            escrow.key(),
+            winner,
+            pot
+        );
+
+        emit!(MatchSettled {
+            match_escrow: escrow.key(),
+            winner,
+            pot,
+        });
+
+        Ok(())
+    }
+
+    /// Begin unbonding the winner's credited balance
+    /// Moves `active` into a new `unlocking` chunk that releases after UNBOND_SECONDS,
+    /// mirroring the active/total/unlocking separation of a delegated-staking ledger
+    pub fn unbond(ctx: Context<Unbond>) -> Result<()> {
+        let escrow = &mut ctx.accounts.match_escrow;
+        let clock = Clock::get()?;
+
+        require!(
+            escrow.winner == Some(ctx.accounts.player.key()),
+            TypingError::NotMatchWinner
+        );
+        require!(escrow.active > 0, TypingError::InsufficientStake);
+        require!(
+            escrow.unlocking.len() < MAX_UNLOCK_CHUNKS,
+            TypingError::TooManyUnlockChunks
+        );
+
+        let amount = escrow.active;
+        escrow.active = 0;
+        escrow.unlocking.push(UnlockChunk {
+            amount,
+            release_ts: clock.unix_timestamp + UNBOND_SECONDS,
+        });
+
+        msg!(
+            "PDA {} - Unbonding {} for {} | Release: {}",
+            escrow.key(),
+            amount,
+            ctx.accounts.player.key(),
+            escrow.unlocking.last().unwrap().release_ts
+        );
+
+        emit!(MatchUnbonded {
+            match_escrow: escrow.key(),
+            player: ctx.accounts.player.key(),
+            amount,
+            release_ts: escrow.unlocking.last().unwrap().release_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw every unlocking chunk whose release timestamp has passed
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let clock = Clock::get()?;
+        let escrow_key = ctx.accounts.match_escrow.key();
+        let escrow = &mut ctx.accounts.match_escrow;
+
+        require!(
+            escrow.winner == Some(ctx.accounts.player.key()),
+            TypingError::NotMatchWinner
+        );
+
+        let now = clock.unix_timestamp;
+        let releasable: u64 = escrow
+            .unlocking
+            .iter()
+            .filter(|chunk| chunk.release_ts <= now)
+            .map(|chunk| chunk.amount)
+            .sum();
+        require!(releasable > 0, TypingError::PrematureWithdrawal);
+
+        escrow.unlocking.retain(|chunk| chunk.release_ts > now);
+
+        let player_a = escrow.player_a;
+        let player_b = escrow.player_b;
+        let nonce = escrow.nonce;
+        let bump = escrow.bump;
+        let seeds: &[&[u8]] = &[
+            b"match_escrow",
+            player_a.as_ref(),
+            player_b.as_ref(),
+            &nonce.to_le_bytes(),
+            &[bump],
+        ];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.player_token_account.to_account_info(),
+                    authority: ctx.accounts.match_escrow.to_account_info(),
+                },
+                &[seeds],
+            ),
+            releasable,
+        )?;
+
+        msg!(
+            "PDA {} - Withdrew {} to {}",
+            escrow_key,
+            releasable,
+            ctx.accounts.player.key()
+        );
+
+        emit!(MatchWithdrawn {
+            match_escrow: escrow_key,
+            player: ctx.accounts.player.key(),
+            amount: releasable,
+        });
+
+        Ok(())
+    }
+
+    // ========================================
+    // Tournaments: ranked leaderboard
+    // ========================================
+
+    /// Create a singleton tournament identified by a creator-chosen id
+    pub fn create_tournament(ctx: Context<CreateTournament>, id: u64, start_ts: i64, end_ts: i64) -> Result<()> {
+        require!(end_ts > start_ts, TypingError::InvalidTournamentWindow);
+
+        let tournament = &mut ctx.accounts.tournament;
+        tournament.id = id;
+        tournament.creator = ctx.accounts.creator.key();
+        tournament.start_ts = start_ts;
+        tournament.end_ts = end_ts;
+        tournament.entry_count = 0;
+        tournament.leaderboard = Vec::new();
+        tournament.bump = ctx.bumps.tournament;
+
+        msg!(
+            "PDA {} - Tournament {} created by {} | Window: {} -> {}",
+            tournament.key(),
+            id,
+            tournament.creator,
+            start_ts,
+            end_ts
+        );
+
+        Ok(())
+    }
+
+    /// Register a player for a tournament, creating their per-tournament entry PDA
+    pub fn register_entry(ctx: Context<RegisterEntry>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        let entry = &mut ctx.accounts.entry;
+
+        entry.tournament = tournament.key();
+        entry.player = ctx.accounts.player.key();
+        entry.has_submitted = false;
+        entry.score = 0;
+        entry.bump = ctx.bumps.entry;
+
+        tournament.entry_count = tournament.entry_count.checked_add(1).unwrap();
+
+        msg!(
+            "PDA {} - Entry registered for {} in tournament {}",
+            entry.key(),
+            entry.player,
+            tournament.id
+        );
+
+        Ok(())
+    }
+
+    /// Submit a finished session's result into the tournament's ranked leaderboard
+    /// Score weights speed by accuracy so error-spam can't inflate ranking
+    pub fn submit_score(ctx: Context<SubmitScore>) -> Result<()> {
+        let clock = Clock::get()?;
+        let entry = &mut ctx.accounts.entry;
+        let tournament = &mut ctx.accounts.tournament;
+
+        require!(
+            clock.unix_timestamp >= tournament.start_ts && clock.unix_timestamp <= tournament.end_ts,
+            TypingError::TournamentNotOpen
+        );
+        require!(!ctx.accounts.session.is_active, TypingError::SessionStillActive);
+        require!(
+            ctx.accounts.session.player == entry.player,
+            TypingError::MismatchedSessionOwner
+        );
+        require!(!entry.has_submitted, TypingError::ScoreAlreadySubmitted);
+        require!(!ctx.accounts.session.consumed, TypingError::SessionAlreadyConsumed);
+
+        let score =
+            (ctx.accounts.session.wpm as u32) * (ctx.accounts.session.accuracy as u32) / 100;
+
+        entry.has_submitted = true;
+        entry.score = score;
+        ctx.accounts.session.consumed = true;
+
+        tournament.leaderboard.push(LeaderEntry {
+            player: entry.player,
+            score,
+        });
+        tournament
+            .leaderboard
+            .sort_unstable_by(|a, b| b.score.cmp(&a.score));
+        tournament.leaderboard.truncate(MAX_LEADERBOARD_SIZE);
+
+        msg!(
+            "PDA {} - Score {} submitted by {} for tournament {}",
+            entry.key(),
+            score,
+            entry.player,
+            tournament.id
+        );
+
+        emit!(ScoreSubmitted {
+            tournament: tournament.key(),
+            player: entry.player,
+            score,
+        });
+
+        Ok(())
+    }
+
+    // ========================================
+    // Races: 1v1 sessions with ELO updates
+    // ========================================
+
+    /// Initialize a player's ELO rating account, starting at the standard 1200
+    pub fn init_player_rating(ctx: Context<InitPlayerRating>) -> Result<()> {
+        let rating = &mut ctx.accounts.player_rating;
+        rating.player = ctx.accounts.player.key();
+        rating.rating = STARTING_RATING;
+        rating.bump = ctx.bumps.player_rating;
+
+        msg!(
+            "PDA {} - Rating initialized for player: {}",
+            rating.key(),
+            rating.player
+        );
+
+        Ok(())
+    }
+
+    /// Bind two typing sessions and a shared word list hash into a race
+    pub fn start_race(ctx: Context<StartRace>, nonce: u64, word_list_hash: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.session_a.player != ctx.accounts.session_b.player,
+            TypingError::InvalidPlayer
+        );
+        require!(
+            ctx.accounts.session_a.is_active && ctx.accounts.session_b.is_active,
+            TypingError::SessionNotActive
+        );
+        require!(
+            ctx.accounts.session_a.active_race.is_none()
+                && ctx.accounts.session_b.active_race.is_none(),
+            TypingError::SessionAlreadyInRace
+        );
+
+        let race_key;
+        {
+            let race = &mut ctx.accounts.race;
+            race.player_a = ctx.accounts.session_a.player;
+            race.player_b = ctx.accounts.session_b.player;
+            race.session_a = ctx.accounts.session_a.key();
+            race.session_b = ctx.accounts.session_b.key();
+            race.nonce = nonce;
+            race.word_list_hash = word_list_hash;
+            race.started_at = clock.unix_timestamp;
+            race.finalized = false;
+            race.bump = ctx.bumps.race;
+            race_key = race.key();
+        }
+
+        ctx.accounts.session_a.active_race = Some(race_key);
+        ctx.accounts.session_b.active_race = Some(race_key);
+
+        msg!(
+            "PDA {} - Race started | {} vs {}",
+            race_key,
+            ctx.accounts.session_a.player,
+            ctx.accounts.session_b.player
+        );
+
+        Ok(())
+    }
+
+    /// Finalize a race once both sessions have ended, applying the standard ELO update
+    pub fn finalize_race(ctx: Context<FinalizeRace>) -> Result<()> {
+        let race = &mut ctx.accounts.race;
+        require!(!race.finalized, TypingError::RaceAlreadyFinalized);
+
+        let session_a = &ctx.accounts.session_a;
+        let session_b = &ctx.accounts.session_b;
+        require!(
+            session_a.key() == race.session_a && session_b.key() == race.session_b,
+            TypingError::MismatchedRaceSession
+        );
+        require!(
+            !session_a.is_active && !session_b.is_active,
+            TypingError::SessionStillActive
+        );
+
+        // Outcome for player A, in basis points: win = 10_000, loss = 0, tie = 5_000
+        let score_a_bps: i64 = if session_a.wpm != session_b.wpm {
+            if session_a.wpm > session_b.wpm {
+                10_000
+            } else {
+                0
+            }
+        } else if session_a.accuracy != session_b.accuracy {
+            if session_a.accuracy > session_b.accuracy {
+                10_000
+            } else {
+                0
+            }
+        } else {
+            5_000
+        };
+        let score_b_bps = 10_000 - score_a_bps;
+
+        let rating_a = ctx.accounts.player_rating_a.rating;
+        let rating_b = ctx.accounts.player_rating_b.rating;
+
+        let expected_a_bps = expected_score_bps(rating_a, rating_b);
+        let expected_b_bps = 10_000 - expected_a_bps;
+
+        let delta_a = K_FACTOR * (score_a_bps - expected_a_bps) / 10_000;
+        let delta_b = K_FACTOR * (score_b_bps - expected_b_bps) / 10_000;
+
+        let new_rating_a = ((rating_a as i64 + delta_a).clamp(MIN_RATING as i64, MAX_RATING as i64)) as u16;
+        let new_rating_b = ((rating_b as i64 + delta_b).clamp(MIN_RATING as i64, MAX_RATING as i64)) as u16;
+
+        ctx.accounts.player_rating_a.rating = new_rating_a;
+        ctx.accounts.player_rating_b.rating = new_rating_b;
+        race.finalized = true;
+        ctx.accounts.session_a.active_race = None;
+        ctx.accounts.session_b.active_race = None;
+
+        msg!(
+            "PDA {} - Race finalized | {} {} -> {} | {} {} -> {}",
+            race.key(),
+            race.player_a,
+            rating_a,
+            new_rating_a,
+            race.player_b,
+            rating_b,
+            new_rating_b
+        );
+
+        emit!(RaceFinalized {
+            race: race.key(),
+            player_a: race.player_a,
+            player_b: race.player_b,
+            rating_a_delta: new_rating_a as i32 - rating_a as i32,
+            rating_b_delta: new_rating_b as i32 - rating_b as i32,
+        });
+
         Ok(())
     }
 }
 
+/// Precomputed `1 / (1 + 10^(d/400)) * 10_000`, rounded to the nearest basis point,
+/// for d in 0..=400. Rating differences beyond 400 are clamped to 400, so the expected
+/// score never drops below 909/10_000 (or rises above 9_091/10_000).
+/// Table-driven instead of a runtime `powf` call: a transcendental float op is not
+/// guaranteed bit-identical across validator CPU/libm implementations, which is a
+/// real determinism hazard for on-chain state.
+const EXPECTED_SCORE_BPS_TABLE: [u16; 401] = [
+    5000, 4986, 4971, 4957, 4942, 4928, 4914, 4899, 4885, 4871,
+    4856, 4842, 4827, 4813, 4799, 4784, 4770, 4756, 4741, 4727,
+    4712, 4698, 4684, 4669, 4655, 4641, 4627, 4612, 4598, 4584,
+    4569, 4555, 4541, 4527, 4512, 4498, 4484, 4470, 4455, 4441,
+    4427, 4413, 4398, 4384, 4370, 4356, 4342, 4328, 4314, 4299,
+    4285, 4271, 4257, 4243, 4229, 4215, 4201, 4187, 4173, 4159,
+    4145, 4131, 4117, 4103, 4089, 4075, 4061, 4048, 4034, 4020,
+    4006, 3992, 3978, 3965, 3951, 3937, 3923, 3910, 3896, 3882,
+    3869, 3855, 3841, 3828, 3814, 3801, 3787, 3773, 3760, 3746,
+    3733, 3720, 3706, 3693, 3679, 3666, 3653, 3639, 3626, 3613,
+    3599, 3586, 3573, 3560, 3546, 3533, 3520, 3507, 3494, 3481,
+    3468, 3455, 3442, 3429, 3416, 3403, 3390, 3377, 3364, 3351,
+    3339, 3326, 3313, 3300, 3288, 3275, 3262, 3250, 3237, 3224,
+    3212, 3199, 3187, 3174, 3162, 3149, 3137, 3125, 3112, 3100,
+    3088, 3075, 3063, 3051, 3039, 3027, 3014, 3002, 2990, 2978,
+    2966, 2954, 2942, 2930, 2918, 2906, 2895, 2883, 2871, 2859,
+    2847, 2836, 2824, 2812, 2801, 2789, 2778, 2766, 2755, 2743,
+    2732, 2720, 2709, 2698, 2686, 2675, 2664, 2652, 2641, 2630,
+    2619, 2608, 2597, 2586, 2575, 2564, 2553, 2542, 2531, 2520,
+    2509, 2498, 2488, 2477, 2466, 2455, 2445, 2434, 2424, 2413,
+    2403, 2392, 2382, 2371, 2361, 2350, 2340, 2330, 2319, 2309,
+    2299, 2289, 2279, 2269, 2259, 2248, 2238, 2228, 2219, 2209,
+    2199, 2189, 2179, 2169, 2159, 2150, 2140, 2130, 2121, 2111,
+    2102, 2092, 2083, 2073, 2064, 2054, 2045, 2035, 2026, 2017,
+    2008, 1998, 1989, 1980, 1971, 1962, 1953, 1944, 1935, 1926,
+    1917, 1908, 1899, 1890, 1881, 1873, 1864, 1855, 1846, 1838,
+    1829, 1821, 1812, 1804, 1795, 1787, 1778, 1770, 1761, 1753,
+    1745, 1736, 1728, 1720, 1712, 1704, 1696, 1687, 1679, 1671,
+    1663, 1655, 1647, 1640, 1632, 1624, 1616, 1608, 1600, 1593,
+    1585, 1577, 1570, 1562, 1555, 1547, 1540, 1532, 1525, 1517,
+    1510, 1502, 1495, 1488, 1481, 1473, 1466, 1459, 1452, 1445,
+    1437, 1430, 1423, 1416, 1409, 1402, 1395, 1389, 1382, 1375,
+    1368, 1361, 1355, 1348, 1341, 1334, 1328, 1321, 1315, 1308,
+    1302, 1295, 1289, 1282, 1276, 1269, 1263, 1257, 1250, 1244,
+    1238, 1231, 1225, 1219, 1213, 1207, 1201, 1195, 1189, 1183,
+    1177, 1171, 1165, 1159, 1153, 1147, 1141, 1135, 1130, 1124,
+    1118, 1112, 1107, 1101, 1095, 1090, 1084, 1079, 1073, 1068,
+    1062, 1057, 1051, 1046, 1041, 1035, 1030, 1025, 1019, 1014,
+    1009, 1004, 998, 993, 988, 983, 978, 973, 968, 963,
+    958, 953, 948, 943, 938, 933, 928, 923, 919, 914,
+    909,
+];
+
+/// Expected score for player A in basis points (909..=9_091), from the standard logistic ELO curve
+/// with the rating difference clamped to ±400
+fn expected_score_bps(rating_a: u16, rating_b: u16) -> i64 {
+    let delta = (rating_b as i32 - rating_a as i32).clamp(-400, 400);
+    if delta >= 0 {
+        EXPECTED_SCORE_BPS_TABLE[delta as usize] as i64
+    } else {
+        10_000 - EXPECTED_SCORE_BPS_TABLE[(-delta) as usize] as i64
+    }
+}
+
 // ========================================
 // Constants
 // ========================================
 
 pub const MAX_ATTEMPTS: u32 = 30;
 
+/// How long a winner's credited balance sits in `unlocking` before it can be withdrawn
+pub const UNBOND_SECONDS: i64 = 3 * 24 * 60 * 60;
+
+/// Cap on simultaneous unlocking chunks per match escrow
+pub const MAX_UNLOCK_CHUNKS: usize = 8;
+
+/// Cap on ranked entries kept on a tournament's leaderboard
+pub const MAX_LEADERBOARD_SIZE: usize = 50;
+
+/// Ceiling on the average WPM implied over a WORD_WINDOW_SIZE-word sliding window
+pub const MAX_PLAUSIBLE_WPM: u16 = 250;
+
+/// Number of recent words averaged over when checking plausible typing speed
+pub const WORD_WINDOW_SIZE: usize = 5;
+
+/// Cost units charged per accepted `type_word`, used for client-side commit budgeting
+pub const COST_UNITS_PER_WORD: u32 = 1;
+
+/// Starting ELO rating for a newly initialized PlayerRating
+pub const STARTING_RATING: u16 = 1200;
+
+/// Standard ELO K-factor used by `finalize_race`
+pub const K_FACTOR: i64 = 32;
+
+pub const MIN_RATING: u16 = 100;
+pub const MAX_RATING: u16 = 3000;
+
+/// Minimum words typed since the last checkpoint before a crank commit is worthwhile
+pub const COMMIT_WORD_THRESHOLD: u32 = 20;
+
+/// Time since the last checkpoint after which a crank commit is allowed regardless of word count
+pub const COMMIT_INTERVAL_SECONDS: i64 = 60;
+
 // ========================================
 // Account Structs
 // ========================================
@@ -323,6 +1009,12 @@ pub struct Update<'info> {
     pub session_token: Option<Account<'info, SessionToken>>,
 }
 
+#[derive(Accounts)]
+pub struct SessionView<'info> {
+    #[account(seeds = [session.player.as_ref()], bump)]
+    pub session: Account<'info, TypingSession>,
+}
+
 #[derive(Accounts)]
 pub struct SaveToRecord<'info> {
     #[account(
@@ -365,6 +1057,228 @@ pub struct CommitInput<'info> {
     pub session: Account<'info, TypingSession>,
 }
 
+/// Account context for the permissionless crank commit
+/// Unlike `CommitInput`, `payer` need not be the session's player
+#[commit]
+#[derive(Accounts)]
+pub struct CrankCommit<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [session.player.as_ref()], bump)]
+    pub session: Account<'info, TypingSession>,
+}
+
+#[derive(Accounts)]
+#[instruction(player_a: Pubkey, player_b: Pubkey, nonce: u64)]
+pub struct Stake<'info> {
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + MatchEscrow::INIT_SPACE,
+        seeds = [b"match_escrow", player_a.as_ref(), player_b.as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub match_escrow: Account<'info, MatchEscrow>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        associated_token::mint = mint,
+        associated_token::authority = match_escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint, token::authority = player)]
+    pub player_token_account: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleMatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"match_escrow", match_escrow.player_a.as_ref(), match_escrow.player_b.as_ref(), &match_escrow.nonce.to_le_bytes()],
+        bump = match_escrow.bump
+    )]
+    pub match_escrow: Account<'info, MatchEscrow>,
+
+    #[account(mut, seeds = [session_a.player.as_ref()], bump)]
+    pub session_a: Account<'info, TypingSession>,
+
+    #[account(mut, seeds = [session_b.player.as_ref()], bump)]
+    pub session_b: Account<'info, TypingSession>,
+}
+
+#[derive(Accounts)]
+pub struct Unbond<'info> {
+    #[account(
+        mut,
+        seeds = [b"match_escrow", match_escrow.player_a.as_ref(), match_escrow.player_b.as_ref(), &match_escrow.nonce.to_le_bytes()],
+        bump = match_escrow.bump
+    )]
+    pub match_escrow: Account<'info, MatchEscrow>,
+
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        mut,
+        seeds = [b"match_escrow", match_escrow.player_a.as_ref(), match_escrow.player_b.as_ref(), &match_escrow.nonce.to_le_bytes()],
+        bump = match_escrow.bump
+    )]
+    pub match_escrow: Account<'info, MatchEscrow>,
+
+    #[account(
+        mut,
+        associated_token::mint = match_escrow.mint,
+        associated_token::authority = match_escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = match_escrow.mint, token::authority = player)]
+    pub player_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateTournament<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Tournament::INIT_SPACE,
+        seeds = [b"tournament", &id.to_le_bytes()],
+        bump
+    )]
+    pub tournament: Account<'info, Tournament>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterEntry<'info> {
+    #[account(mut, seeds = [b"tournament", &tournament.id.to_le_bytes()], bump = tournament.bump)]
+    pub tournament: Account<'info, Tournament>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + TournamentEntry::INIT_SPACE,
+        seeds = [b"tournament_entry", tournament.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, TournamentEntry>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitScore<'info> {
+    #[account(mut, seeds = [b"tournament", &tournament.id.to_le_bytes()], bump = tournament.bump)]
+    pub tournament: Account<'info, Tournament>,
+
+    #[account(
+        mut,
+        seeds = [b"tournament_entry", tournament.key().as_ref(), entry.player.as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Account<'info, TournamentEntry>,
+
+    #[account(mut, seeds = [session.player.as_ref()], bump)]
+    pub session: Account<'info, TypingSession>,
+}
+
+#[derive(Accounts)]
+pub struct InitPlayerRating<'info> {
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerRating::INIT_SPACE,
+        seeds = [b"player_rating", player.key().as_ref()],
+        bump
+    )]
+    pub player_rating: Account<'info, PlayerRating>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct StartRace<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RaceSession::INIT_SPACE,
+        seeds = [b"race", session_a.player.as_ref(), session_b.player.as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub race: Account<'info, RaceSession>,
+
+    #[account(mut, seeds = [session_a.player.as_ref()], bump)]
+    pub session_a: Account<'info, TypingSession>,
+
+    #[account(mut, seeds = [session_b.player.as_ref()], bump)]
+    pub session_b: Account<'info, TypingSession>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeRace<'info> {
+    #[account(
+        mut,
+        seeds = [b"race", race.player_a.as_ref(), race.player_b.as_ref(), &race.nonce.to_le_bytes()],
+        bump = race.bump
+    )]
+    pub race: Account<'info, RaceSession>,
+
+    #[account(mut, seeds = [session_a.player.as_ref()], bump)]
+    pub session_a: Account<'info, TypingSession>,
+
+    #[account(mut, seeds = [session_b.player.as_ref()], bump)]
+    pub session_b: Account<'info, TypingSession>,
+
+    #[account(
+        mut,
+        seeds = [b"player_rating", race.player_a.as_ref()],
+        bump = player_rating_a.bump
+    )]
+    pub player_rating_a: Account<'info, PlayerRating>,
+
+    #[account(
+        mut,
+        seeds = [b"player_rating", race.player_b.as_ref()],
+        bump = player_rating_b.bump
+    )]
+    pub player_rating_b: Account<'info, PlayerRating>,
+}
+
 // ========================================
 // Account Data
 // ========================================
@@ -390,6 +1304,24 @@ pub struct TypingSession {
     pub started_at: i64,
     /// Session end timestamp
     pub ended_at: Option<i64>,
+    /// Ring buffer of the timestamps of the last WORD_WINDOW_SIZE accepted words,
+    /// indexed by `words_typed % WORD_WINDOW_SIZE`; used to bound average WPM over
+    /// a sliding window rather than a single (whole-second-resolution) gap
+    pub recent_word_ts: [i64; WORD_WINDOW_SIZE],
+    /// Accumulated cost units, one `COST_UNITS_PER_WORD` per accepted word
+    pub cost_units: u32,
+    /// `words_typed` as of the last committed checkpoint
+    pub words_at_last_commit: u32,
+    /// Timestamp of the last committed checkpoint
+    pub last_commit_ts: i64,
+    /// The RaceSession this session is currently bound to, if any; a session can only
+    /// be bound into one race at a time, cleared once that race is finalized
+    pub active_race: Option<Pubkey>,
+    /// Whether this finished session has already been spent on a one-shot, outcome-sensitive
+    /// action (settling a staked match or submitting a tournament score). Unlike `active_race`,
+    /// which `finalize_race` clears, this stays set once set, since those actions pay out or rank
+    /// a single result rather than something that can be safely repeated. Reset only on `initialize`.
+    pub consumed: bool,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, InitSpace)]
@@ -424,6 +1356,89 @@ pub struct PersonalRecord {
     pub attempts: Vec<TypingAttempt>,
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct MatchEscrow {
+    /// Whether this escrow has been set up by the first `stake` call
+    pub initialized: bool,
+    pub player_a: Pubkey,
+    pub player_b: Pubkey,
+    /// Caller-chosen nonce, allows the same pair of players to run multiple matches
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub vault_bump: u8,
+    pub bonded_a: u64,
+    pub bonded_b: u64,
+    /// Whether `settle_match` has already run
+    pub settled: bool,
+    pub winner: Option<Pubkey>,
+    /// Winner's credited balance that has not yet started unbonding
+    pub active: u64,
+    /// Winner's balance in transit to withdrawal, each chunk releasable after its timestamp
+    #[max_len(MAX_UNLOCK_CHUNKS)]
+    pub unlocking: Vec<UnlockChunk>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, InitSpace)]
+pub struct UnlockChunk {
+    pub amount: u64,
+    pub release_ts: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Tournament {
+    pub id: u64,
+    pub creator: Pubkey,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub entry_count: u32,
+    pub bump: u8,
+    /// Top scores seen so far, sorted descending, capped at MAX_LEADERBOARD_SIZE
+    #[max_len(MAX_LEADERBOARD_SIZE)]
+    pub leaderboard: Vec<LeaderEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, InitSpace)]
+pub struct LeaderEntry {
+    pub player: Pubkey,
+    pub score: u32,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TournamentEntry {
+    pub tournament: Pubkey,
+    pub player: Pubkey,
+    pub has_submitted: bool,
+    pub score: u32,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PlayerRating {
+    pub player: Pubkey,
+    pub rating: u16,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RaceSession {
+    pub player_a: Pubkey,
+    pub player_b: Pubkey,
+    pub session_a: Pubkey,
+    pub session_b: Pubkey,
+    /// Caller-chosen nonce, allows the same pair of players to run repeat races
+    pub nonce: u64,
+    pub word_list_hash: [u8; 32],
+    pub started_at: i64,
+    pub finalized: bool,
+    pub bump: u8,
+}
+
 // ========================================
 // Events
 // ========================================
@@ -464,6 +1479,51 @@ pub struct TypingSessionSaved {
     pub attempt_number: u32,
 }
 
+#[event]
+pub struct MatchStaked {
+    pub match_escrow: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MatchSettled {
+    pub match_escrow: Pubkey,
+    pub winner: Pubkey,
+    pub pot: u64,
+}
+
+#[event]
+pub struct MatchUnbonded {
+    pub match_escrow: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub release_ts: i64,
+}
+
+#[event]
+pub struct MatchWithdrawn {
+    pub match_escrow: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ScoreSubmitted {
+    pub tournament: Pubkey,
+    pub player: Pubkey,
+    pub score: u32,
+}
+
+#[event]
+pub struct RaceFinalized {
+    pub race: Pubkey,
+    pub player_a: Pubkey,
+    pub player_b: Pubkey,
+    pub rating_a_delta: i32,
+    pub rating_b_delta: i32,
+}
+
 // ========================================
 // Errors
 // ========================================
@@ -480,4 +1540,38 @@ pub enum TypingError {
     MaxAttemptsReached,
     #[msg("Session is still active")]
     SessionStillActive,
+    #[msg("Stake amount is insufficient")]
+    InsufficientStake,
+    #[msg("Signer is not one of the two staked players")]
+    InvalidPlayer,
+    #[msg("Typing session owner does not match the escrowed player")]
+    MismatchedSessionOwner,
+    #[msg("Session has already been spent on a prior match settlement or score submission")]
+    SessionAlreadyConsumed,
+    #[msg("Match has already been settled")]
+    MatchAlreadySettled,
+    #[msg("Signer is not the winner of this match")]
+    NotMatchWinner,
+    #[msg("Too many pending unlock chunks")]
+    TooManyUnlockChunks,
+    #[msg("No unlocking balance has passed its release timestamp yet")]
+    PrematureWithdrawal,
+    #[msg("Stake mint does not match the mint recorded by this escrow")]
+    MintMismatch,
+    #[msg("Tournament end time must be after its start time")]
+    InvalidTournamentWindow,
+    #[msg("Tournament is not currently open for score submissions")]
+    TournamentNotOpen,
+    #[msg("Entry has already submitted a score for this tournament")]
+    ScoreAlreadySubmitted,
+    #[msg("Implausible typing speed over the recent word window")]
+    ImplausibleSpeed,
+    #[msg("Race has already been finalized")]
+    RaceAlreadyFinalized,
+    #[msg("Session does not match the one bound to this race")]
+    MismatchedRaceSession,
+    #[msg("Session is already bound to another unfinalized race")]
+    SessionAlreadyInRace,
+    #[msg("Checkpoint threshold not yet reached, crank commit skipped")]
+    CommitNotWarranted,
 }
\ No newline at end of file